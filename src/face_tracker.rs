@@ -0,0 +1,121 @@
+//! Holds detected face boxes across frames so a face that the detector
+//! misses for a frame or two keeps being blurred instead of flashing
+//! through unblurred.
+
+/// How many frames a track is kept alive after its last confident match.
+const DEFAULT_HOLD_FRAMES: u32 = 6;
+
+/// Minimum intersection-over-union for a detection to be considered the same
+/// face as an existing track.
+const IOU_MATCH_THRESHOLD: f32 = 0.3;
+
+/// Exponential moving average weight given to the freshly detected box.
+const SMOOTHING_FACTOR: f32 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FaceBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FaceBox {
+    fn iou(&self, other: &FaceBox) -> f32 {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+
+        if right <= left || bottom <= top {
+            return 0.0;
+        }
+
+        let intersection = (right - left) as f32 * (bottom - top) as f32;
+        let area_self = (self.width * self.height) as f32;
+        let area_other = (other.width * other.height) as f32;
+
+        intersection / (area_self + area_other - intersection)
+    }
+
+    fn smoothed(&self, previous: &FaceBox) -> FaceBox {
+        let lerp = |new: u32, old: u32| -> u32 {
+            (SMOOTHING_FACTOR * new as f32 + (1.0 - SMOOTHING_FACTOR) * old as f32).round() as u32
+        };
+
+        FaceBox {
+            x: lerp(self.x, previous.x),
+            y: lerp(self.y, previous.y),
+            width: lerp(self.width, previous.width),
+            height: lerp(self.height, previous.height),
+        }
+    }
+}
+
+struct Track {
+    bbox: FaceBox,
+    can_stay_for: u32,
+}
+
+/// Persists face boxes across frames using a short lookahead/hold buffer:
+/// each tracked region keeps being reported for `hold_frames` frames after
+/// its last matched detection, so a single missed frame doesn't let a face
+/// show up unblurred.
+pub struct FaceTracker {
+    tracks: Vec<Track>,
+    hold_frames: u32,
+}
+
+impl FaceTracker {
+    pub fn new() -> Self {
+        FaceTracker::with_hold_frames(DEFAULT_HOLD_FRAMES)
+    }
+
+    pub fn with_hold_frames(hold_frames: u32) -> Self {
+        FaceTracker { tracks: Vec::new(), hold_frames }
+    }
+
+    /// Matches `detections` against existing tracks by IoU, refreshing
+    /// matched tracks and decrementing the hold counter on unmatched ones,
+    /// then returns the (possibly smoothed) boxes that should be blurred
+    /// this frame.
+    pub fn update(&mut self, detections: Vec<FaceBox>) -> Vec<FaceBox> {
+        let mut matched = vec![false; self.tracks.len()];
+
+        for detection in detections {
+            let best_match = self.tracks.iter()
+                .enumerate()
+                .filter(|(index, _)| !matched[*index])
+                .map(|(index, track)| (index, track.bbox.iou(&detection)))
+                .filter(|(_, iou)| *iou >= IOU_MATCH_THRESHOLD)
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+            match best_match {
+                Some((index, _)) => {
+                    let track = &mut self.tracks[index];
+                    track.bbox = detection.smoothed(&track.bbox);
+                    track.can_stay_for = self.hold_frames;
+                    matched[index] = true;
+                }
+                None => {
+                    self.tracks.push(Track { bbox: detection, can_stay_for: self.hold_frames });
+                    matched.push(true);
+                }
+            }
+        }
+
+        // An unmatched track is dropped only once its counter is already at
+        // zero, so it is still reported for `hold_frames` unmatched frames.
+        let mut index = 0;
+        self.tracks.retain_mut(|track| {
+            let keep = matched[index] || track.can_stay_for > 0;
+            if !matched[index] {
+                track.can_stay_for = track.can_stay_for.saturating_sub(1);
+            }
+            index += 1;
+            keep
+        });
+
+        self.tracks.iter().map(|track| track.bbox).collect()
+    }
+}
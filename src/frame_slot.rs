@@ -0,0 +1,41 @@
+//! Bounded single-slot hand-off for the most recently processed frame. A
+//! producer that outpaces its consumer overwrites the pending frame
+//! (dropping the stale one) instead of a queue growing without bound.
+
+use std::sync::{Arc, Condvar, Mutex};
+
+use image::RgbaImage;
+
+#[derive(Clone)]
+pub struct FrameSlot {
+    state: Arc<(Mutex<Option<Arc<RgbaImage>>>, Condvar)>,
+}
+
+impl FrameSlot {
+    pub fn new() -> Self {
+        FrameSlot { state: Arc::new((Mutex::new(None), Condvar::new())) }
+    }
+
+    /// Publishes the latest frame, replacing any frame the consumer hasn't
+    /// picked up yet.
+    pub fn publish(&self, frame: Arc<RgbaImage>) {
+        let (slot, condvar) = &*self.state;
+        *slot.lock().unwrap() = Some(frame);
+        condvar.notify_one();
+    }
+
+    /// Blocks until a frame is published, then returns it and clears the
+    /// slot.
+    pub fn take_blocking(&self) -> Arc<RgbaImage> {
+        let (slot, condvar) = &*self.state;
+        let mut guard = slot.lock().unwrap();
+
+        loop {
+            if let Some(frame) = guard.take() {
+                return frame;
+            }
+
+            guard = condvar.wait(guard).unwrap();
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! GPU blur backend: the raw camera frame is uploaded once as a texture and
+//! blurred in full by a two-pass separable Gaussian (horizontal then
+//! vertical) fragment shader, so each detected face rectangle can be read
+//! back out of the already-blurred result instead of re-running the shader
+//! per face.
+
+use gl::types::{GLchar, GLenum, GLint, GLsizei, GLuint};
+use image::RgbaImage;
+
+use crate::face_tracker::FaceBox;
+
+const VERTEX_SHADER: &[u8] = b"
+#version 450 core
+layout (location = 0) in vec2 position;
+layout (location = 1) in vec2 tex_coord;
+out vec2 v_tex_coord;
+void main() {
+    v_tex_coord = tex_coord;
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+\0";
+
+const BLUR_FRAGMENT_SHADER: &[u8] = b"
+#version 450 core
+in vec2 v_tex_coord;
+out vec4 frag_color;
+
+uniform sampler2D u_texture;
+uniform vec2 u_texel_size;
+uniform vec2 u_direction;
+uniform int u_radius;
+
+void main() {
+    vec4 sum = texture(u_texture, v_tex_coord) * 0.227027;
+    float total_weight = 0.227027;
+
+    for (int i = 1; i <= u_radius; i++) {
+        float weight = exp(-float(i * i) / (2.0 * float(u_radius * u_radius)));
+        vec2 offset = u_direction * u_texel_size * float(i);
+
+        sum += texture(u_texture, v_tex_coord + offset) * weight;
+        sum += texture(u_texture, v_tex_coord - offset) * weight;
+        total_weight += weight * 2.0;
+    }
+
+    frag_color = sum / total_weight;
+}
+\0";
+
+/// Which implementation `loop_faces` uses to blur a detected face rectangle.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BlurBackend {
+    /// `anonymize::apply` with the configured `AnonymizeMode` on the CPU,
+    /// works everywhere.
+    Cpu,
+    /// Two-pass separable Gaussian fragment shader, needs GL 4.5.
+    Gpu,
+}
+
+/// Owns the GL objects needed to run the two-pass blur shader over an
+/// uploaded frame texture and read sub-rectangles of the result back.
+pub struct GpuBlur {
+    program: GLuint,
+    frame_texture: GLuint,
+    ping_pong_textures: [GLuint; 2],
+    ping_pong_framebuffers: [GLuint; 2],
+    quad_vao: GLuint,
+    quad_vbo: GLuint,
+    frame_width: u32,
+    frame_height: u32,
+    // Whether `TexImage2D` has allocated storage for the current
+    // frame_width/frame_height yet; once it has, later uploads only need
+    // `TexSubImage2D` to replace the contents.
+    storage_allocated: bool,
+}
+
+impl GpuBlur {
+    pub fn new() -> Self {
+        unsafe {
+            let program = link_program(compile_shader(VERTEX_SHADER, gl::VERTEX_SHADER), compile_shader(BLUR_FRAGMENT_SHADER, gl::FRAGMENT_SHADER));
+
+            let mut frame_texture = 0;
+            gl::GenTextures(1, &mut frame_texture);
+
+            let mut ping_pong_textures = [0; 2];
+            gl::GenTextures(2, ping_pong_textures.as_mut_ptr());
+
+            let mut ping_pong_framebuffers = [0; 2];
+            gl::GenFramebuffers(2, ping_pong_framebuffers.as_mut_ptr());
+
+            let (quad_vao, quad_vbo) = create_fullscreen_quad();
+
+            GpuBlur {
+                program,
+                frame_texture,
+                ping_pong_textures,
+                ping_pong_framebuffers,
+                quad_vao,
+                quad_vbo,
+                frame_width: 0,
+                frame_height: 0,
+                storage_allocated: false,
+            }
+        }
+    }
+
+    /// Uploads the raw camera frame once per call to `get_image_from_frame`
+    /// so every face rectangle in the frame can sample it. Texture storage
+    /// is allocated once per resolution; later uploads only replace the
+    /// pixel contents.
+    pub fn upload_frame(&mut self, frame: &RgbaImage) {
+        let resized = frame.width() != self.frame_width || frame.height() != self.frame_height;
+        self.frame_width = frame.width();
+        self.frame_height = frame.height();
+
+        unsafe {
+            if !self.storage_allocated || resized {
+                gl::BindTexture(gl::TEXTURE_2D, self.frame_texture);
+                gl::TexImage2D(
+                    gl::TEXTURE_2D, 0, gl::RGBA8 as GLint,
+                    self.frame_width as GLsizei, self.frame_height as GLsizei, 0,
+                    gl::RGBA, gl::UNSIGNED_BYTE, frame.as_raw().as_ptr() as *const _,
+                );
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+
+                for &texture in &self.ping_pong_textures {
+                    gl::BindTexture(gl::TEXTURE_2D, texture);
+                    gl::TexImage2D(
+                        gl::TEXTURE_2D, 0, gl::RGBA8 as GLint,
+                        self.frame_width as GLsizei, self.frame_height as GLsizei, 0,
+                        gl::RGBA, gl::UNSIGNED_BYTE, std::ptr::null(),
+                    );
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+                    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+                }
+
+                self.storage_allocated = true;
+            } else {
+                gl::BindTexture(gl::TEXTURE_2D, self.frame_texture);
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D, 0, 0, 0,
+                    self.frame_width as GLsizei, self.frame_height as GLsizei,
+                    gl::RGBA, gl::UNSIGNED_BYTE, frame.as_raw().as_ptr() as *const _,
+                );
+            }
+        }
+    }
+
+    /// Runs the horizontal-then-vertical blur passes once over the whole
+    /// uploaded frame, leaving the result bound as the source for
+    /// `read_region`. Must be called once per frame before any
+    /// `read_region` calls, not once per face: the blur is the same
+    /// full-frame post-process regardless of how many faces sample it.
+    pub fn blur_frame(&mut self, blur_intensity: f32) {
+        let radius = (blur_intensity * 4.0).round().max(1.0) as i32;
+        let texel_size = (1.0 / self.frame_width as f32, 1.0 / self.frame_height as f32);
+
+        unsafe {
+            gl::UseProgram(self.program);
+            gl::Viewport(0, 0, self.frame_width as GLsizei, self.frame_height as GLsizei);
+            gl::BindVertexArray(self.quad_vao);
+
+            set_uniform_1i(self.program, "u_radius\0", radius);
+            set_uniform_2f(self.program, "u_texel_size\0", texel_size.0, texel_size.1);
+
+            // Horizontal pass: frame texture -> ping_pong[0]
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.ping_pong_framebuffers[0]);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, self.ping_pong_textures[0], 0);
+            gl::BindTexture(gl::TEXTURE_2D, self.frame_texture);
+            set_uniform_2f(self.program, "u_direction\0", 1.0, 0.0);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+            // Vertical pass: ping_pong[0] -> ping_pong[1]
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.ping_pong_framebuffers[1]);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, self.ping_pong_textures[1], 0);
+            gl::BindTexture(gl::TEXTURE_2D, self.ping_pong_textures[0]);
+            set_uniform_2f(self.program, "u_direction\0", 0.0, 1.0);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        }
+    }
+
+    /// Reads `face_box` back out of the frame blurred by the last
+    /// `blur_frame` call, as an `RgbaImage` crop ready to be pasted into
+    /// the output frame the same way the CPU path's blurred crop is.
+    pub fn read_region(&self, face_box: FaceBox) -> RgbaImage {
+        let mut pixels = vec![0u8; (face_box.width * face_box.height * 4) as usize];
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.ping_pong_framebuffers[1]);
+            gl::ReadPixels(
+                face_box.x as GLint, face_box.y as GLint, face_box.width as GLsizei, face_box.height as GLsizei,
+                gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        RgbaImage::from_raw(face_box.width, face_box.height, pixels).unwrap()
+    }
+}
+
+impl Drop for GpuBlur {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteTextures(1, &self.frame_texture);
+            gl::DeleteTextures(2, self.ping_pong_textures.as_ptr());
+            gl::DeleteFramebuffers(2, self.ping_pong_framebuffers.as_ptr());
+            gl::DeleteVertexArrays(1, &self.quad_vao);
+            gl::DeleteBuffers(1, &self.quad_vbo);
+        }
+    }
+}
+
+unsafe fn compile_shader(source: &[u8], kind: GLenum) -> GLuint {
+    let shader = gl::CreateShader(kind);
+    gl::ShaderSource(shader, 1, &(source.as_ptr() as *const GLchar), std::ptr::null());
+    gl::CompileShader(shader);
+
+    let mut success = gl::FALSE as GLint;
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+
+    if success != gl::TRUE as GLint {
+        panic!("failed to compile shader: {}", shader_info_log(shader));
+    }
+
+    shader
+}
+
+unsafe fn link_program(vertex_shader: GLuint, fragment_shader: GLuint) -> GLuint {
+    let program = gl::CreateProgram();
+    gl::AttachShader(program, vertex_shader);
+    gl::AttachShader(program, fragment_shader);
+    gl::LinkProgram(program);
+
+    let mut success = gl::FALSE as GLint;
+    gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+
+    if success != gl::TRUE as GLint {
+        panic!("failed to link blur shader program: {}", program_info_log(program));
+    }
+
+    gl::DeleteShader(vertex_shader);
+    gl::DeleteShader(fragment_shader);
+    program
+}
+
+unsafe fn shader_info_log(shader: GLuint) -> String {
+    let mut length = 0;
+    gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut length);
+
+    let mut buffer = vec![0u8; length.max(0) as usize];
+    gl::GetShaderInfoLog(shader, length, std::ptr::null_mut(), buffer.as_mut_ptr() as *mut GLchar);
+
+    String::from_utf8_lossy(&buffer).trim_end_matches('\0').to_string()
+}
+
+unsafe fn program_info_log(program: GLuint) -> String {
+    let mut length = 0;
+    gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut length);
+
+    let mut buffer = vec![0u8; length.max(0) as usize];
+    gl::GetProgramInfoLog(program, length, std::ptr::null_mut(), buffer.as_mut_ptr() as *mut GLchar);
+
+    String::from_utf8_lossy(&buffer).trim_end_matches('\0').to_string()
+}
+
+unsafe fn create_fullscreen_quad() -> (GLuint, GLuint) {
+    #[rustfmt::skip]
+    let vertices: [f32; 16] = [
+        -1.0, -1.0, 0.0, 0.0,
+         1.0, -1.0, 1.0, 0.0,
+        -1.0,  1.0, 0.0, 1.0,
+         1.0,  1.0, 1.0, 1.0,
+    ];
+
+    let mut vao = 0;
+    let mut vbo = 0;
+    gl::GenVertexArrays(1, &mut vao);
+    gl::GenBuffers(1, &mut vbo);
+
+    gl::BindVertexArray(vao);
+    gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+    gl::BufferData(
+        gl::ARRAY_BUFFER,
+        (vertices.len() * std::mem::size_of::<f32>()) as isize,
+        vertices.as_ptr() as *const _,
+        gl::STATIC_DRAW,
+    );
+
+    let stride = 4 * std::mem::size_of::<f32>() as GLsizei;
+    gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+    gl::EnableVertexAttribArray(0);
+    gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, stride, (2 * std::mem::size_of::<f32>()) as *const _);
+    gl::EnableVertexAttribArray(1);
+
+    (vao, vbo)
+}
+
+unsafe fn set_uniform_1i(program: GLuint, name: &str, value: i32) {
+    let location = gl::GetUniformLocation(program, name.as_ptr() as *const GLchar);
+    gl::Uniform1i(location, value);
+}
+
+unsafe fn set_uniform_2f(program: GLuint, name: &str, x: f32, y: f32) {
+    let location = gl::GetUniformLocation(program, name.as_ptr() as *const GLchar);
+    gl::Uniform2f(location, x, y);
+}
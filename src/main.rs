@@ -1,19 +1,36 @@
 // #![allow(dead_code, unused)]
 
+mod anonymize;
+mod face_tracker;
+mod frame_slot;
+mod gpu_blur;
+mod recorder;
+mod server;
+mod virtual_camera;
+
 use std::borrow::Cow;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use glutin_window::GlutinWindow;
 use graphics::{image as create_image};
 use image::{DynamicImage, GenericImageView, Pixel, Rgba, RgbaImage};
-use image::imageops::{blur, FilterType};
+use image::imageops::FilterType;
 use nokhwa::{Camera, CameraFormat, FrameFormat};
 use opengl_graphics::{GlGraphics, OpenGL, Texture, TextureSettings};
-use piston::RenderEvent;
+use piston::{Button, Key, PressEvent, RenderEvent};
 use piston::event_loop::{Events, EventSettings};
 use piston::window::WindowSettings;
 use rustface::{Detector, ImageData};
 
+use anonymize::AnonymizeMode;
+use face_tracker::{FaceBox, FaceTracker};
+use frame_slot::FrameSlot;
+use gpu_blur::{BlurBackend, GpuBlur};
+use recorder::GifRecorder;
+use virtual_camera::OutputTarget;
+
 #[derive(PartialEq)]
 struct Resolution {
     width: u32,
@@ -23,8 +40,16 @@ struct Resolution {
 struct Settings {
     framerate: u32,
     blur_intensity: f32,
+    blur_backend: BlurBackend,
+    anonymize_mode: AnonymizeMode,
     capture: Resolution,
     detection: Resolution,
+    // When set, also serve the processed frames over HTTP (MJPEG `/stream`,
+    // JPEG `/snapshot`) on this port, in addition to the windowed renderer.
+    server_port: Option<u16>,
+    recording_output_path: PathBuf,
+    max_recording_length: Duration,
+    output_target: OutputTarget,
 }
 
 impl Default for Settings {
@@ -33,7 +58,13 @@ impl Default for Settings {
             capture: Resolution { width: 1280, height: 720 },
             detection: Resolution { width: 640 - 300, height: 480 - 300 },
             blur_intensity: 1.5,
+            blur_backend: BlurBackend::Cpu,
+            anonymize_mode: AnonymizeMode::FastBlur,
             framerate: 30,
+            server_port: None,
+            recording_output_path: PathBuf::from("./output.gif"),
+            max_recording_length: Duration::from_secs(10),
+            output_target: OutputTarget::Window,
         }
     }
 }
@@ -72,11 +103,30 @@ fn main() {
 
     let mut gl = GlGraphics::new(opengl);
     let mut events = Events::new(EventSettings::new());
+    let mut tracker = FaceTracker::new();
+    let mut gpu_blur = match options.blur_backend {
+        BlurBackend::Cpu => None,
+        BlurBackend::Gpu => Some(GpuBlur::new()),
+    };
+    let frame_slot = options.server_port.map(server::spawn);
+    let mut recorder = GifRecorder::new(options.recording_output_path.clone(), options.max_recording_length, options.framerate);
+    let virtual_camera_slot = match &options.output_target {
+        OutputTarget::Window => None,
+        OutputTarget::VirtualCamera { device } => Some(virtual_camera::spawn(device.clone(), options.capture.width, options.capture.height, options.framerate)),
+    };
 
     while let Some(event) = events.next(&mut window) {
+        // Press `R` to toggle recording the anonymized feed to a GIF.
+        if let Some(Button::Keyboard(Key::R)) = event.press_args() {
+            match recorder.is_recording() {
+                true => recorder.stop(),
+                false => recorder.start(),
+            }
+        }
+
         if let Some(args) = event.render_args() {
             let buffer = camera.frame_raw().unwrap();
-            let texture = get_image_from_frame(&mut *detector, buffer, &options);
+            let texture = get_image_from_frame(&mut *detector, buffer, &options, &mut tracker, gpu_blur.as_mut(), frame_slot.as_ref(), &mut recorder, virtual_camera_slot.as_ref());
 
             gl.draw(args.viewport(), |c, g| {
                 create_image(&texture, c.transform, g);
@@ -89,9 +139,21 @@ fn get_millis(duration: Duration) -> u64 {
     duration.as_secs() * 1000u64 + u64::from(duration.subsec_nanos() / 1_000_000)
 }
 
+fn anonymize_face(source: &DynamicImage, face_box: FaceBox, options: &Settings, gpu_blur: &mut Option<&mut GpuBlur>) -> RgbaImage {
+    match (options.blur_backend, gpu_blur) {
+        (BlurBackend::Gpu, Some(gpu_blur)) => gpu_blur.read_region(face_box),
+        _ => {
+            let cropped = source.view(face_box.x, face_box.y, face_box.width, face_box.height).to_image();
+            anonymize::apply(options.anonymize_mode, &cropped, options.blur_intensity)
+        }
+    }
+}
+
 fn loop_faces(
     detector: &mut dyn Detector,
     options: &Settings,
+    tracker: &mut FaceTracker,
+    mut gpu_blur: Option<&mut GpuBlur>,
     source: &DynamicImage,
     vase: &DynamicImage,
     callback: fn(&mut RgbaImage, u32, u32, Rgba<u8>) -> (),
@@ -101,16 +163,24 @@ fn loop_faces(
     let mut image_data = ImageData::new(&luma, options.detection.width, options.detection.height);
     let faces = detector.detect(&mut image_data);
 
-    for face in faces {
+    let detections = faces.iter().map(|face| {
         let bbox = face.bbox();
-        let box_x = bbox.x() as u32;
-        let box_y = bbox.y() as u32;
+        FaceBox { x: bbox.x() as u32, y: bbox.y() as u32, width: bbox.width(), height: bbox.height() }
+    }).collect();
+
+    if let (BlurBackend::Gpu, Some(gpu_blur)) = (options.blur_backend, gpu_blur.as_mut()) {
+        // Blur the whole frame once here; each face below only reads its
+        // sub-rect back out, instead of re-running the full-frame passes
+        // per face.
+        gpu_blur.upload_frame(&source.to_rgba8());
+        gpu_blur.blur_frame(options.blur_intensity);
+    }
 
-        let cropped = source.view(box_x, box_y, bbox.width(), bbox.height()).to_image();
-        let blurred = blur(&cropped, options.blur_intensity);
+    for face_box in tracker.update(detections) {
+        let blurred = anonymize_face(source, face_box, options, &mut gpu_blur);
 
         for (x, y, pixel) in blurred.enumerate_pixels() {
-            callback(&mut output, x + box_x, y + box_y, pixel.to_rgba());
+            callback(&mut output, x + face_box.x, y + face_box.y, pixel.to_rgba());
         }
     }
 
@@ -120,13 +190,15 @@ fn loop_faces(
 fn process(
     detector: &mut dyn Detector,
     options: &Settings,
+    tracker: &mut FaceTracker,
+    gpu_blur: Option<&mut GpuBlur>,
     source: DynamicImage,
 ) -> RgbaImage {
     let low_resolution_image: DynamicImage = source
         .resize_exact(options.detection.width, options.detection.height, FilterType::Nearest);
 
     let blank_image = DynamicImage::new_rgba8(options.detection.width, options.detection.height);
-    let output_temp = loop_faces(detector, &options, &low_resolution_image, &blank_image, |output, x, y, pixel: Rgba<u8>| {
+    let output_temp = loop_faces(detector, &options, tracker, gpu_blur, &low_resolution_image, &blank_image, |output, x, y, pixel: Rgba<u8>| {
         output.put_pixel(x, y, pixel);
     });
 
@@ -151,24 +223,50 @@ fn process(
 fn process_light(
     detector: &mut dyn Detector,
     options: &Settings,
+    tracker: &mut FaceTracker,
+    gpu_blur: Option<&mut GpuBlur>,
     source: DynamicImage,
 ) -> RgbaImage {
-    loop_faces(detector, &options, &source, &source, |output, x, y, pixel: Rgba<u8>| {
+    loop_faces(detector, &options, tracker, gpu_blur, &source, &source, |output, x, y, pixel: Rgba<u8>| {
         output.put_pixel(x, y, pixel);
     })
 }
 
-fn get_image_from_frame(detector: &mut dyn Detector, buffer: Cow<[u8]>, options: &Settings) -> Texture {
+fn get_image_from_frame(
+    detector: &mut dyn Detector,
+    buffer: Cow<[u8]>,
+    options: &Settings,
+    tracker: &mut FaceTracker,
+    gpu_blur: Option<&mut GpuBlur>,
+    frame_slot: Option<&FrameSlot>,
+    recorder: &mut GifRecorder,
+    virtual_camera_slot: Option<&FrameSlot>,
+) -> Texture {
     let now = Instant::now();
     let image: DynamicImage = image::load_from_memory(&buffer).unwrap();
 
     // When capture and detection has the same resolution we can save some work
     // by avoiding lowering the resolution of the source
     let output = match options.detection == options.capture {
-        true => process_light(detector, options, image),
-        false => process(detector, options, image),
+        true => process_light(detector, options, tracker, gpu_blur, image),
+        false => process(detector, options, tracker, gpu_blur, image),
     };
 
+    // Wrapped once so the server and virtual-camera sinks below share a
+    // single allocation instead of each cloning the full frame; the recorder
+    // still copies the frame while a recording is active.
+    let output = Arc::new(output);
+
+    if let Some(frame_slot) = frame_slot {
+        frame_slot.publish(output.clone());
+    }
+
+    recorder.push_frame(&output);
+
+    if let Some(virtual_camera_slot) = virtual_camera_slot {
+        virtual_camera_slot.publish(output.clone());
+    }
+
     let settings = TextureSettings::new();
     let texture = Texture::from_image(&output, &settings);
 
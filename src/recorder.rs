@@ -0,0 +1,116 @@
+//! Records the anonymized feed to a looping GIF so a clip can be shared
+//! without re-identifying anyone in it. Encoding happens on a dedicated
+//! worker thread so `stop` can return immediately and live capture keeps
+//! running while a recording is written out.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+struct CapturedFrame {
+    image: RgbaImage,
+    captured_at: Instant,
+}
+
+struct EncodeJob {
+    output_path: PathBuf,
+    framerate: u32,
+    frames: Vec<CapturedFrame>,
+}
+
+/// Collects processed frames while active and hands them off to a worker
+/// thread to be written out as a GIF on `stop`, keyed off the configured
+/// `framerate` and `max_recording_length`.
+pub struct GifRecorder {
+    output_path: PathBuf,
+    max_recording_length: Duration,
+    framerate: u32,
+    frames: Vec<CapturedFrame>,
+    started_at: Option<Instant>,
+    job_sender: Sender<EncodeJob>,
+}
+
+impl GifRecorder {
+    pub fn new(output_path: PathBuf, max_recording_length: Duration, framerate: u32) -> Self {
+        let (job_sender, job_receiver) = channel::<EncodeJob>();
+
+        thread::spawn(move || {
+            for job in job_receiver {
+                if let Err(error) = encode_job(job) {
+                    println!("Failed to encode recording: {}", error);
+                }
+            }
+        });
+
+        GifRecorder { output_path, max_recording_length, framerate, frames: Vec::new(), started_at: None, job_sender }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.frames.clear();
+        self.started_at = Some(Instant::now());
+    }
+
+    /// Feeds a processed frame into the recording while it is active,
+    /// stopping automatically once `max_recording_length` is reached.
+    pub fn push_frame(&mut self, frame: &RgbaImage) {
+        let Some(started_at) = self.started_at else { return; };
+
+        if started_at.elapsed() >= self.max_recording_length {
+            self.stop();
+            return;
+        }
+
+        self.frames.push(CapturedFrame { image: frame.clone(), captured_at: Instant::now() });
+    }
+
+    /// Hands the collected frames off to the encoder thread and clears the
+    /// buffer; the GIF is written in the background, off the render loop.
+    pub fn stop(&mut self) {
+        if self.started_at.take().is_none() || self.frames.is_empty() {
+            return;
+        }
+
+        let job = EncodeJob {
+            output_path: self.output_path.clone(),
+            framerate: self.framerate,
+            frames: std::mem::take(&mut self.frames),
+        };
+
+        let _ = self.job_sender.send(job);
+    }
+}
+
+fn encode_job(job: EncodeJob) -> image::ImageResult<()> {
+    let started_at = Instant::now();
+
+    let file = std::fs::File::create(&job.output_path)?;
+    let mut encoder = GifEncoder::new(file);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let fallback_frame_duration = Duration::from_secs_f64(1.0 / job.framerate as f64);
+
+    for index in 0..job.frames.len() {
+        // Each frame's delay reflects the actual time until the next
+        // capture rather than the nominal framerate, so GIF timing tracks
+        // real capture jitter.
+        let frame_duration = match job.frames.get(index + 1) {
+            Some(next) => next.captured_at.duration_since(job.frames[index].captured_at),
+            None => fallback_frame_duration,
+        };
+
+        let image = job.frames[index].image.clone();
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, Delay::from_saturating_duration(frame_duration)))?;
+    }
+
+    println!("Encoded {} frames in {} ms to {}", job.frames.len(), started_at.elapsed().as_millis(), job.output_path.display());
+
+    Ok(())
+}
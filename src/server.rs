@@ -0,0 +1,87 @@
+//! Headless MJPEG streaming so the anonymized feed can be consumed by
+//! browsers or other tools over HTTP instead of only the local Piston
+//! window.
+
+use std::thread;
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use futures_util::stream;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, RgbaImage};
+use tokio::sync::watch;
+
+use crate::frame_slot::FrameSlot;
+
+const JPEG_QUALITY: u8 = 80;
+
+fn encode_jpeg(frame: &RgbaImage) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY);
+    encoder.encode_image(&DynamicImage::ImageRgba8(frame.clone())).unwrap();
+    bytes
+}
+
+async fn stream_handler(latest: web::Data<watch::Receiver<Vec<u8>>>) -> HttpResponse {
+    let receiver = latest.get_ref().clone();
+
+    let body = stream::unfold(receiver, |mut receiver| async move {
+        if receiver.changed().await.is_err() {
+            return None;
+        }
+
+        let jpeg = receiver.borrow().clone();
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(b"--frame\r\nContent-Type: image/jpeg\r\nContent-Length: ");
+        chunk.extend_from_slice(jpeg.len().to_string().as_bytes());
+        chunk.extend_from_slice(b"\r\n\r\n");
+        chunk.extend_from_slice(&jpeg);
+        chunk.extend_from_slice(b"\r\n");
+
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(chunk)), receiver))
+    });
+
+    HttpResponse::Ok()
+        .content_type("multipart/x-mixed-replace; boundary=frame")
+        .streaming(body)
+}
+
+async fn snapshot_handler(latest: web::Data<watch::Receiver<Vec<u8>>>) -> HttpResponse {
+    let jpeg = latest.get_ref().borrow().clone();
+
+    HttpResponse::Ok().content_type("image/jpeg").body(jpeg)
+}
+
+/// Spawns the HTTP server on its own thread and returns the slot the render
+/// loop can publish finished frames into, so capture and serving run
+/// concurrently. GET `/stream` returns a `multipart/x-mixed-replace` MJPEG
+/// stream, GET `/snapshot` returns the latest frame as a single JPEG.
+pub fn spawn(port: u16) -> FrameSlot {
+    let frame_slot = FrameSlot::new();
+    let encoder_slot = frame_slot.clone();
+    let (jpeg_sender, jpeg_receiver) = watch::channel(Vec::new());
+
+    thread::spawn(move || loop {
+        let frame = encoder_slot.take_blocking();
+        if jpeg_sender.send(encode_jpeg(&frame)).is_err() {
+            break;
+        }
+    });
+
+    thread::spawn(move || {
+        actix_web::rt::System::new().block_on(async move {
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(jpeg_receiver.clone()))
+                    .route("/stream", web::get().to(stream_handler))
+                    .route("/snapshot", web::get().to(snapshot_handler))
+            })
+                .bind(("0.0.0.0", port))
+                .unwrap()
+                .run()
+                .await
+                .unwrap();
+        });
+    });
+
+    frame_slot
+}
@@ -0,0 +1,98 @@
+//! Publishes the anonymized feed to a system virtual camera device (e.g.
+//! v4l2loopback on Linux) via a GStreamer `appsrc` pipeline, so the output
+//! becomes selectable as a webcam in other applications like Zoom, Meet or
+//! OBS.
+
+use std::thread;
+
+use gstreamer::prelude::*;
+use gstreamer_app::AppSrc;
+use image::RgbaImage;
+
+use crate::frame_slot::FrameSlot;
+
+/// Where processed frames are sent: the local Piston window only, or the
+/// window and a virtual camera device at once.
+pub enum OutputTarget {
+    Window,
+    VirtualCamera { device: String },
+}
+
+/// Converts an RGBA buffer into the YUY2 packed format `v4l2loopback`
+/// expects, using the standard BT.601 luma/chroma weights.
+fn rgba_to_yuy2(frame: &RgbaImage) -> Vec<u8> {
+    let (width, height) = frame.dimensions();
+    let mut yuy2 = Vec::with_capacity((width * height * 2) as usize);
+
+    for y in 0..height {
+        let mut x = 0;
+
+        while x < width {
+            let left = frame.get_pixel(x, y).0;
+            let right = frame.get_pixel((x + 1).min(width - 1), y).0;
+
+            let y0 = rgb_to_luma(left[0], left[1], left[2]);
+            let y1 = rgb_to_luma(right[0], right[1], right[2]);
+            let (u, v) = rgb_to_chroma(left[0], left[1], left[2]);
+
+            yuy2.extend_from_slice(&[y0, u, y1, v]);
+            x += 2;
+        }
+    }
+
+    yuy2
+}
+
+fn rgb_to_luma(r: u8, g: u8, b: u8) -> u8 {
+    (16.0 + (0.257 * r as f32 + 0.504 * g as f32 + 0.098 * b as f32)).round() as u8
+}
+
+fn rgb_to_chroma(r: u8, g: u8, b: u8) -> (u8, u8) {
+    let u = (128.0 + (-0.148 * r as f32 - 0.291 * g as f32 + 0.439 * b as f32)).round() as u8;
+    let v = (128.0 + (0.439 * r as f32 - 0.368 * g as f32 - 0.071 * b as f32)).round() as u8;
+    (u, v)
+}
+
+/// Builds an `appsrc ! v4l2sink` pipeline targeting `device` and returns the
+/// slot the render loop can publish finished frames into; capture and
+/// publishing then run concurrently.
+pub fn spawn(device: String, width: u32, height: u32, framerate: u32) -> FrameSlot {
+    let frame_slot = FrameSlot::new();
+    let pipeline_slot = frame_slot.clone();
+
+    thread::spawn(move || {
+        gstreamer::init().expect("failed to initialize gstreamer");
+
+        let pipeline_description = format!(
+            "appsrc name=src is-live=true block=true format=time \
+             caps=video/x-raw,format=YUY2,width={width},height={height},framerate={framerate}/1 \
+             ! videoconvert ! v4l2sink device={device}"
+        );
+
+        let pipeline = gstreamer::parse::launch(&pipeline_description)
+            .expect("failed to build virtual camera pipeline")
+            .downcast::<gstreamer::Pipeline>()
+            .expect("pipeline description did not produce a Pipeline");
+
+        let app_src = pipeline
+            .by_name("src")
+            .expect("appsrc element missing from pipeline")
+            .downcast::<AppSrc>()
+            .expect("src element is not an appsrc");
+
+        pipeline.set_state(gstreamer::State::Playing).expect("failed to start virtual camera pipeline");
+
+        loop {
+            let frame = pipeline_slot.take_blocking();
+            let yuy2 = rgba_to_yuy2(&frame);
+            let buffer = gstreamer::Buffer::from_slice(yuy2);
+            if app_src.push_buffer(buffer).is_err() {
+                break;
+            }
+        }
+
+        let _ = pipeline.set_state(gstreamer::State::Null);
+    });
+
+    frame_slot
+}